@@ -1,9 +1,20 @@
-use opentelemetry::trace::TracerProvider;
+use opentelemetry::{
+    KeyValue,
+    propagation::{Extractor, Injector},
+    trace::TracerProvider,
+};
+use opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge;
 use opentelemetry_otlp::WithExportConfig;
-use opentelemetry_sdk::{metrics::SdkMeterProvider, trace::SdkTracerProvider};
+use opentelemetry_sdk::{
+    Resource,
+    logs::SdkLoggerProvider,
+    metrics::SdkMeterProvider,
+    propagation::{BaggagePropagator, TextMapCompositePropagator, TraceContextPropagator},
+    trace::SdkTracerProvider,
+};
 use thiserror::Error;
 use tracing::{Subscriber, info, level_filters::LevelFilter};
-use tracing_opentelemetry::MetricsLayer;
+use tracing_opentelemetry::{MetricsLayer, OpenTelemetrySpanExt};
 use tracing_subscriber::{Layer, Registry, layer::SubscriberExt};
 
 pub struct TelemetryConfig {
@@ -12,25 +23,43 @@ pub struct TelemetryConfig {
     pub log_level: tracing::Level,
     pub metrics_endpoint: Option<String>,
     pub tracing_endpoint: Option<String>,
+    pub logs_endpoint: Option<String>,
     pub protocol: opentelemetry_otlp::Protocol,
+    pub resource_attributes: Vec<KeyValue>,
+    pub propagator: Propagator,
+    pub prometheus_enabled: bool,
+}
+
+/// Selects which W3C/vendor trace-context propagator is installed globally.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Propagator {
+    /// Composite of W3C `TraceContext` + `Baggage`.
+    #[default]
+    TraceContext,
+    /// AWS X-Ray propagation format.
+    XRay,
 }
 
 #[derive(Error, Debug)]
 pub enum TelemetryError {
-    #[error("Failed to create metric exporter: {0}")]
-    MetricExporter(#[from] opentelemetry_otlp::ExporterBuildError),
+    #[error("Failed to build OTLP exporter: {0}")]
+    ExporterBuild(#[from] opentelemetry_otlp::ExporterBuildError),
     #[error("Invalid configuration: {0}")]
     Configuration(String),
     #[error("Provider shutdown failed: {0}")]
     Shutdown(String),
     #[error("Missing tracer provider")]
     MissingTracerProvider,
+    #[error("Failed to encode metrics: {0}")]
+    Encode(String),
 }
 
 pub struct TelemetryBuilder {
     config: TelemetryConfig,
     meter_provider: Option<SdkMeterProvider>,
     tracer_provider: Option<SdkTracerProvider>,
+    logger_provider: Option<SdkLoggerProvider>,
+    prometheus_registry: Option<prometheus::Registry>,
 }
 
 impl Drop for TelemetryBuilder {
@@ -48,6 +77,12 @@ impl Drop for TelemetryBuilder {
                 tracing::error!("Failed to shutdown tracer provider: {}", e);
             }
         }
+
+        if let Some(provider) = &self.logger_provider {
+            if let Err(e) = provider.shutdown() {
+                tracing::error!("Failed to shutdown logger provider: {}", e);
+            }
+        }
     }
 }
 
@@ -88,6 +123,19 @@ impl TelemetryBuilder {
         Ok(())
     }
 
+    pub fn init_propagation(&self) {
+        let propagator: Box<dyn opentelemetry::propagation::TextMapPropagator + Send + Sync> =
+            match self.config.propagator {
+                Propagator::TraceContext => Box::new(TextMapCompositePropagator::new(vec![
+                    Box::new(TraceContextPropagator::new()),
+                    Box::new(BaggagePropagator::new()),
+                ])),
+                Propagator::XRay => Box::new(opentelemetry_aws::trace::XrayPropagator::default()),
+            };
+
+        opentelemetry::global::set_text_map_propagator(propagator);
+    }
+
     pub fn new(service_name: impl Into<String>) -> Self {
         Self {
             config: TelemetryConfig {
@@ -96,10 +144,16 @@ impl TelemetryBuilder {
                 log_level: tracing::Level::INFO,
                 metrics_endpoint: std::env::var("METRICS_ENDPOINT").ok(),
                 tracing_endpoint: std::env::var("TRACING_ENDPOINT").ok(),
-                protocol: opentelemetry_otlp::Protocol::HttpBinary,
+                logs_endpoint: std::env::var("LOGS_ENDPOINT").ok(),
+                protocol: protocol_from_env().unwrap_or(opentelemetry_otlp::Protocol::HttpBinary),
+                resource_attributes: Vec::new(),
+                propagator: Propagator::default(),
+                prometheus_enabled: false,
             },
             meter_provider: None,
             tracer_provider: None,
+            logger_provider: None,
+            prometheus_registry: None,
         }
     }
 
@@ -118,37 +172,129 @@ impl TelemetryBuilder {
         self
     }
 
+    pub fn with_logs_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.config.logs_endpoint = Some(endpoint.into());
+        self
+    }
+
+    pub fn with_service_version(mut self, version: impl Into<String>) -> Self {
+        self.config.service_version = Some(version.into());
+        self
+    }
+
+    pub fn with_resource_attribute(
+        mut self,
+        key: impl Into<opentelemetry::Key>,
+        value: impl Into<opentelemetry::Value>,
+    ) -> Self {
+        self.config
+            .resource_attributes
+            .push(KeyValue::new(key, value));
+        self
+    }
+
+    pub fn with_protocol(mut self, protocol: opentelemetry_otlp::Protocol) -> Self {
+        self.config.protocol = protocol;
+        self
+    }
+
+    pub fn with_propagator(mut self, propagator: Propagator) -> Self {
+        self.config.propagator = propagator;
+        self
+    }
+
+    pub fn with_prometheus(mut self) -> Self {
+        self.config.prometheus_enabled = true;
+        self
+    }
+
+    /// Returns the current metrics snapshot in Prometheus text exposition
+    /// format, suitable for serving from a `/metrics` route.
+    pub fn prometheus_metrics(&self) -> Result<String, TelemetryError> {
+        let registry = self.prometheus_registry.as_ref().ok_or_else(|| {
+            TelemetryError::Configuration("Prometheus exporter is not enabled".to_string())
+        })?;
+
+        let metric_families = registry.gather();
+        let mut buffer = Vec::new();
+        prometheus::TextEncoder::new()
+            .encode(&metric_families, &mut buffer)
+            .map_err(|e| TelemetryError::Encode(e.to_string()))?;
+
+        String::from_utf8(buffer).map_err(|e| TelemetryError::Encode(e.to_string()))
+    }
+
     fn build_registry(&mut self) -> Result<impl Subscriber + Send + Sync, TelemetryError> {
         let logging_layer = build_logging_layer()?;
         let service_name = self.config.service_name.clone();
         let mut layers: Vec<Box<dyn Layer<Registry> + Send + Sync>> = vec![logging_layer];
 
-        if let Some(endpoint) = &self.config.metrics_endpoint {
-            let provider = build_meter_provider(endpoint.to_owned(), service_name.clone())?;
+        let resource = build_resource(&self.config);
+
+        if self.config.metrics_endpoint.is_some() || self.config.prometheus_enabled {
+            let mut builder = SdkMeterProvider::builder().with_resource(resource.clone());
+
+            if let Some(endpoint) = &self.config.metrics_endpoint {
+                builder = builder.with_periodic_exporter(build_otlp_metric_exporter(
+                    endpoint.to_owned(),
+                    self.config.protocol,
+                )?);
+            }
+
+            if self.config.prometheus_enabled {
+                let prometheus_registry = prometheus::Registry::new();
+                let prometheus_exporter = opentelemetry_prometheus::exporter()
+                    .with_registry(prometheus_registry.clone())
+                    .build()
+                    .map_err(|e| TelemetryError::Configuration(e.to_string()))?;
+                builder = builder.with_reader(prometheus_exporter);
+                self.prometheus_registry = Some(prometheus_registry);
+            }
+
+            let provider = builder.build();
             self.meter_provider = Some(provider.clone());
             layers.push(build_metrics_exporter(provider)?);
         }
 
         if let Some(endpoint) = &self.config.tracing_endpoint {
-            let provider = build_tracer_provider(endpoint.to_owned(), service_name.clone())?;
+            let provider =
+                build_tracer_provider(endpoint.to_owned(), self.config.protocol, resource.clone())?;
             self.tracer_provider = Some(provider.clone());
             layers.push(build_tracing_exporter(provider, service_name.clone())?);
         }
 
+        if let Some(endpoint) = &self.config.logs_endpoint {
+            let provider =
+                build_logger_provider(endpoint.to_owned(), self.config.protocol, resource.clone())?;
+            self.logger_provider = Some(provider.clone());
+            layers.push(build_logging_exporter(provider)?);
+        }
+
         let registry = Registry::default().with(layers);
 
         Ok(registry)
     }
 }
 
-fn build_logging_layer() -> Result<Box<dyn Layer<Registry> + Send + Sync>, TelemetryError> {
-    let env_log_level = std::env::var("LOG_LEVEL")
+fn protocol_from_env() -> Option<opentelemetry_otlp::Protocol> {
+    match std::env::var("OTEL_EXPORTER_OTLP_PROTOCOL").ok()?.as_str() {
+        "grpc" => Some(opentelemetry_otlp::Protocol::Grpc),
+        "http/json" => Some(opentelemetry_otlp::Protocol::HttpJson),
+        "http/protobuf" => Some(opentelemetry_otlp::Protocol::HttpBinary),
+        _ => None,
+    }
+}
+
+fn env_log_level() -> LevelFilter {
+    std::env::var("LOG_LEVEL")
         .unwrap_or("info".to_string())
         .parse()
         .ok()
-        .unwrap_or(LevelFilter::INFO);
+        .unwrap_or(LevelFilter::INFO)
+}
 
-    let target = tracing_subscriber::filter::Targets::new().with_default(env_log_level);
+fn build_logging_layer() -> Result<Box<dyn Layer<Registry> + Send + Sync>, TelemetryError> {
+    let target = tracing_subscriber::filter::Targets::new().with_default(env_log_level());
 
     Ok(tracing_subscriber::fmt::layer()
         .with_level(true)
@@ -156,27 +302,44 @@ fn build_logging_layer() -> Result<Box<dyn Layer<Registry> + Send + Sync>, Telem
         .boxed())
 }
 
-fn build_meter_provider(
-    endpoint: String,
-    service_name: String,
-) -> Result<SdkMeterProvider, TelemetryError> {
-    let metrics_exporter = opentelemetry_otlp::MetricExporter::builder()
-        .with_http()
-        .with_protocol(opentelemetry_otlp::Protocol::HttpBinary)
-        .with_endpoint(endpoint)
-        .build()
-        .map_err(TelemetryError::MetricExporter)?;
+fn build_resource(config: &TelemetryConfig) -> Resource {
+    let mut builder = Resource::builder().with_service_name(config.service_name.clone());
 
-    let meter_provider = opentelemetry_sdk::metrics::SdkMeterProvider::builder()
-        .with_periodic_exporter(metrics_exporter)
-        .with_resource(
-            opentelemetry_sdk::Resource::builder()
-                .with_service_name(service_name)
-                .build(),
-        )
-        .build();
+    if let Some(version) = &config.service_version {
+        builder = builder.with_attribute(KeyValue::new(
+            opentelemetry_semantic_conventions::resource::SERVICE_VERSION,
+            version.clone(),
+        ));
+    }
 
-    Ok(meter_provider)
+    builder = builder.with_attribute(KeyValue::new(
+        opentelemetry_semantic_conventions::resource::HOST_NAME,
+        gethostname::gethostname().to_string_lossy().into_owned(),
+    ));
+
+    builder
+        .with_attributes(config.resource_attributes.clone())
+        .build()
+}
+
+fn build_otlp_metric_exporter(
+    endpoint: String,
+    protocol: opentelemetry_otlp::Protocol,
+) -> Result<opentelemetry_otlp::MetricExporter, TelemetryError> {
+    if protocol == opentelemetry_otlp::Protocol::Grpc {
+        opentelemetry_otlp::MetricExporter::builder()
+            .with_tonic()
+            .with_endpoint(endpoint)
+            .build()
+            .map_err(TelemetryError::ExporterBuild)
+    } else {
+        opentelemetry_otlp::MetricExporter::builder()
+            .with_http()
+            .with_protocol(protocol)
+            .with_endpoint(endpoint)
+            .build()
+            .map_err(TelemetryError::ExporterBuild)
+    }
 }
 
 fn build_metrics_exporter(
@@ -187,22 +350,27 @@ fn build_metrics_exporter(
 
 fn build_tracer_provider(
     endpoint: String,
-    service_name: String,
+    protocol: opentelemetry_otlp::Protocol,
+    resource: Resource,
 ) -> Result<SdkTracerProvider, TelemetryError> {
-    let exporter = opentelemetry_otlp::SpanExporter::builder()
-        .with_http()
-        .with_protocol(opentelemetry_otlp::Protocol::HttpBinary)
-        .with_endpoint(endpoint)
-        .build()
-        .map_err(TelemetryError::MetricExporter)?;
+    let exporter = if protocol == opentelemetry_otlp::Protocol::Grpc {
+        opentelemetry_otlp::SpanExporter::builder()
+            .with_tonic()
+            .with_endpoint(endpoint)
+            .build()
+            .map_err(TelemetryError::ExporterBuild)?
+    } else {
+        opentelemetry_otlp::SpanExporter::builder()
+            .with_http()
+            .with_protocol(protocol)
+            .with_endpoint(endpoint)
+            .build()
+            .map_err(TelemetryError::ExporterBuild)?
+    };
 
     Ok(SdkTracerProvider::builder()
         .with_batch_exporter(exporter)
-        .with_resource(
-            opentelemetry_sdk::Resource::builder()
-                .with_service_name(service_name)
-                .build(),
-        )
+        .with_resource(resource)
         .build())
 }
 
@@ -214,3 +382,54 @@ fn build_tracing_exporter(
         .with_tracer(tracer_provider.tracer(service_name))
         .boxed())
 }
+
+fn build_logger_provider(
+    endpoint: String,
+    protocol: opentelemetry_otlp::Protocol,
+    resource: Resource,
+) -> Result<SdkLoggerProvider, TelemetryError> {
+    let exporter = if protocol == opentelemetry_otlp::Protocol::Grpc {
+        opentelemetry_otlp::LogExporter::builder()
+            .with_tonic()
+            .with_endpoint(endpoint)
+            .build()
+            .map_err(TelemetryError::ExporterBuild)?
+    } else {
+        opentelemetry_otlp::LogExporter::builder()
+            .with_http()
+            .with_protocol(protocol)
+            .with_endpoint(endpoint)
+            .build()
+            .map_err(TelemetryError::ExporterBuild)?
+    };
+
+    Ok(SdkLoggerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(resource)
+        .build())
+}
+
+fn build_logging_exporter(
+    logger_provider: SdkLoggerProvider,
+) -> Result<Box<dyn Layer<Registry> + Send + Sync>, TelemetryError> {
+    let target = tracing_subscriber::filter::Targets::new().with_default(env_log_level());
+
+    Ok(OpenTelemetryTracingBridge::new(&logger_provider)
+        .with_filter(target)
+        .boxed())
+}
+
+/// Injects the current tracing span's context into outbound request headers
+/// using the globally configured propagator.
+pub fn inject_context(injector: &mut dyn Injector) {
+    let cx = tracing::Span::current().context();
+    opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&cx, injector);
+    });
+}
+
+/// Extracts a remote trace context from inbound request headers using the
+/// globally configured propagator.
+pub fn extract_context(extractor: &dyn Extractor) -> opentelemetry::Context {
+    opentelemetry::global::get_text_map_propagator(|propagator| propagator.extract(extractor))
+}
@@ -27,7 +27,7 @@ pub enum RendererError {
 }
 
 impl Renderer {
-    pub fn new(directory: String) -> Result<Self, RendererError> {
+    pub fn new(directory: String, assets_directory: String) -> Result<Self, RendererError> {
         let cache_key = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
             .unwrap();
@@ -43,7 +43,10 @@ impl Renderer {
 
         handlebars.register_helper(
             "digest_asset",
-            Box::new(DigestAssetHandlebarsHelper { cache_key }),
+            Box::new(DigestAssetHandlebarsHelper::new(
+                cache_key,
+                assets_directory,
+            )),
         );
 
         #[allow(unused_mut)]
@@ -1,9 +1,72 @@
+use faster_hex::hex_string;
 use handlebars::{
     Context, Handlebars, Helper, HelperDef, HelperResult, Output, RenderContext, RenderErrorReason,
 };
+use sha2::{Digest, Sha256};
+use std::{collections::HashMap, fs, path::PathBuf, sync::Mutex, time::SystemTime};
+
+struct CachedDigest {
+    modified: SystemTime,
+    len: u64,
+    digest: String,
+}
 
 pub(crate) struct DigestAssetHandlebarsHelper {
     pub(crate) cache_key: u64,
+    pub(crate) assets_dir: PathBuf,
+    digests: Mutex<HashMap<String, CachedDigest>>,
+}
+
+impl DigestAssetHandlebarsHelper {
+    pub(crate) fn new(cache_key: u64, assets_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            cache_key,
+            assets_dir: assets_dir.into(),
+            digests: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Hashes the file's current bytes, re-hashing whenever its mtime or
+    /// size changes so edits made without a process restart are reflected.
+    /// The file is read outside the cache lock so a miss on one asset
+    /// doesn't block concurrent lookups of other assets; two threads racing
+    /// on the same cold file may redundantly hash it once.
+    fn digest_for(&self, file: &str) -> String {
+        let Ok(metadata) = fs::metadata(self.assets_dir.join(file)) else {
+            return self.cache_key.to_string();
+        };
+        let Ok(modified) = metadata.modified() else {
+            return self.cache_key.to_string();
+        };
+        let len = metadata.len();
+
+        if let Ok(digests) = self.digests.lock() {
+            if let Some(cached) = digests.get(file) {
+                if cached.modified == modified && cached.len == len {
+                    return cached.digest.clone();
+                }
+            }
+        }
+
+        let Ok(bytes) = fs::read(self.assets_dir.join(file)) else {
+            return self.cache_key.to_string();
+        };
+
+        let digest = hex_string(&Sha256::digest(&bytes)[..8]);
+
+        if let Ok(mut digests) = self.digests.lock() {
+            digests.insert(
+                file.to_string(),
+                CachedDigest {
+                    modified,
+                    len,
+                    digest: digest.clone(),
+                },
+            );
+        }
+
+        digest
+    }
 }
 
 impl HelperDef for DigestAssetHandlebarsHelper {
@@ -20,11 +83,13 @@ impl HelperDef for DigestAssetHandlebarsHelper {
             .map(|v| v.value())
             .ok_or(RenderErrorReason::ParamNotFoundForIndex("digest_asset", 0))?;
 
+        let file = file.to_string().replace("\"", "");
+
         let mut path = "/assets/".to_string();
 
-        path.push_str(&file.to_string().replace("\"", ""));
+        path.push_str(&file);
         path.push_str("?v=");
-        path.push_str(&self.cache_key.to_string());
+        path.push_str(&self.digest_for(&file));
 
         out.write(&path)?;
         Ok(())